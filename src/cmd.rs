@@ -0,0 +1,55 @@
+use std::path::Path;
+use std::process::{Command, ExitStatus, Output};
+
+/// Resolves and spawns platform-correct executables. On Windows, `npm`,
+/// `pnpm`, and `yarn` are `.cmd` shims and `node` ships as `node.exe`; on
+/// Unix the plain names are already correct. Centralizing that lookup here
+/// (rather than scattering `Command::new("npm")` everywhere) keeps the
+/// wrapper reliably cross-platform instead of silently broken on Windows.
+pub struct Cmd;
+
+impl Cmd {
+    /// Maps a logical command name to the executable name for this platform.
+    pub fn resolve(name: &str) -> String {
+        if cfg!(target_os = "windows") {
+            match name {
+                "npm" | "pnpm" | "yarn" => format!("{}.cmd", name),
+                "node" => "node.exe".to_string(),
+                other => other.to_string(),
+            }
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// Builds a `Command` for `name`, already resolved for this platform.
+    pub fn command(name: &str) -> Command {
+        Command::new(Self::resolve(name))
+    }
+
+    /// Runs `name` with inherited stdio, e.g. an interactive package-manager
+    /// install, returning the child's exit status.
+    pub fn run_inherited(
+        name: &str,
+        args: &[&str],
+        current_dir: Option<&Path>,
+    ) -> std::io::Result<ExitStatus> {
+        let mut command = Self::command(name);
+        command.args(args);
+        if let Some(dir) = current_dir {
+            command.current_dir(dir);
+        }
+        command.status()
+    }
+
+    /// Runs `name` with captured (not inherited) stdio, e.g. a `--version`
+    /// availability probe.
+    pub fn run_captured(name: &str, args: &[&str]) -> std::io::Result<Output> {
+        Self::command(name).args(args).output()
+    }
+
+    /// Whether `name` can be spawned at all on this platform.
+    pub fn is_available(name: &str) -> bool {
+        Self::run_captured(name, &["--version"]).is_ok()
+    }
+}
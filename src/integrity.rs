@@ -0,0 +1,233 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use reqwest::blocking::Client;
+use sha2::{Digest, Sha256};
+
+/// A Subresource-Integrity style digest, e.g. `sha256-<base64>`.
+pub struct Sri {
+    pub algorithm: &'static str,
+    pub digest: Vec<u8>,
+}
+
+impl Sri {
+    fn sha256(bytes: &[u8]) -> Self {
+        let digest = Sha256::digest(bytes).to_vec();
+        Sri {
+            algorithm: "sha256",
+            digest,
+        }
+    }
+
+    pub fn to_sri_string(&self) -> String {
+        format!(
+            "{}-{}",
+            self.algorithm,
+            BASE64.encode(&self.digest)
+        )
+    }
+
+    fn to_hex_string(&self) -> String {
+        self.digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+/// Verifies `tarball_bytes` against a checksum asset published alongside the
+/// release (a `SHA256SUMS` file or a standalone `*.sha256` file), mirroring
+/// how npm lockfiles pin an `integrity` hash for each resolved tarball.
+///
+/// Returns the computed SRI string on success so callers can log/persist it.
+pub fn verify_tarball(
+    client: &Client,
+    release_json: &serde_json::Value,
+    tarball_name_hint: &str,
+    tarball_bytes: &[u8],
+) -> Result<String, Box<dyn std::error::Error>> {
+    let actual = Sri::sha256(tarball_bytes);
+    let actual_sri = actual.to_sri_string();
+
+    let expected_hex = find_expected_digest(client, release_json, tarball_name_hint)?;
+
+    match expected_hex {
+        Some(expected_hex) => {
+            if expected_hex.eq_ignore_ascii_case(&actual.to_hex_string()) {
+                Ok(actual_sri)
+            } else {
+                Err(format!(
+                    "checksum mismatch: expected sha256 {} but downloaded tarball hashes to {} ({})",
+                    expected_hex, actual.to_hex_string(), actual_sri
+                )
+                .into())
+            }
+        }
+        None => Err(
+            "no SHA256SUMS or *.sha256 checksum asset found on this release; refusing to extract an unverified tarball"
+                .into(),
+        ),
+    }
+}
+
+/// Looks through `release_json["assets"]` for a checksum file and returns the
+/// expected hex digest for `tarball_name_hint`, if one can be found.
+fn find_expected_digest(
+    client: &Client,
+    release_json: &serde_json::Value,
+    tarball_name_hint: &str,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let assets = match release_json["assets"].as_array() {
+        Some(assets) => assets,
+        None => return Ok(None),
+    };
+
+    for asset in assets {
+        let name = match asset["name"].as_str() {
+            Some(name) => name,
+            None => continue,
+        };
+        let lower = name.to_lowercase();
+        if !lower.contains("sha256") {
+            continue;
+        }
+        let download_url = match asset["browser_download_url"].as_str() {
+            Some(url) => url,
+            None => continue,
+        };
+
+        let response = client
+            .get(download_url)
+            .header("User-Agent", "package-installer-cli-rust-wrapper")
+            .send()?;
+        if !response.status().is_success() {
+            continue;
+        }
+        let body = response.text()?;
+
+        if let Some(digest) = parse_checksum_body(&body, tarball_name_hint) {
+            return Ok(Some(digest));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Parses either a `sha256sum`-style `SHA256SUMS` file (`<hex>  <filename>`
+/// per line) or a bare `*.sha256` file containing a single hex digest.
+///
+/// In a `SHA256SUMS`-style file, only the digest whose filename contains
+/// `tarball_name_hint` is returned - an unrelated hash in the same file is
+/// worse than no hash at all, since it would simply fail to match the real
+/// download. A bare single-digest file is returned as-is; it names no file,
+/// so it is not checked against `tarball_name_hint`.
+fn parse_checksum_body(body: &str, tarball_name_hint: &str) -> Option<String> {
+    let lines: Vec<&str> = body.lines().filter(|l| !l.trim().is_empty()).collect();
+
+    // Bare `*.sha256` file: a single line with just the hex digest and no
+    // filename at all. A one-entry SHA256SUMS-style line ("<hex>  <file>")
+    // must NOT take this shortcut - it still needs the name check below.
+    if lines.len() == 1 {
+        let mut parts = lines[0].split_whitespace();
+        let candidate = parts.next().unwrap_or("");
+        if parts.next().is_none() && is_hex_sha256(candidate) {
+            return Some(candidate.to_lowercase());
+        }
+    }
+
+    // `SHA256SUMS`-style file: `<hex>  <filename>` per line.
+    for line in &lines {
+        let mut parts = line.split_whitespace();
+        let hex = parts.next().unwrap_or("");
+        let file = parts.next().unwrap_or("");
+        if is_hex_sha256(hex) && file.contains(tarball_name_hint) {
+            return Some(hex.to_lowercase());
+        }
+    }
+
+    None
+}
+
+/// Finds a real uploaded release asset to install from (e.g. a `.tar.gz`
+/// build artifact), as opposed to GitHub's autogenerated `tarball_url`
+/// source archive. Only an asset like this can have a meaningful checksum
+/// published alongside it, since `tarball_url` is regenerated on demand and
+/// never itself appears in `assets`.
+pub fn pick_archive_asset(release_json: &serde_json::Value) -> Option<(String, String)> {
+    let assets = release_json["assets"].as_array()?;
+
+    for asset in assets {
+        let name = asset["name"].as_str()?;
+        let lower = name.to_lowercase();
+        if lower.contains("sha256") || lower.ends_with(".sha256") {
+            continue; // This is a checksum file, not the archive itself.
+        }
+        if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+            if let Some(url) = asset["browser_download_url"].as_str() {
+                return Some((url.to_string(), name.to_string()));
+            }
+        }
+    }
+
+    None
+}
+
+fn is_hex_sha256(candidate: &str) -> bool {
+    candidate.len() == 64 && candidate.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bare_sha256_file() {
+        let body = "deadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef\n";
+        assert_eq!(
+            parse_checksum_body(body, "cli-v1.0.0.tar.gz"),
+            Some("deadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_sha256sums_style_file_matching_by_name() {
+        let body = "\
+aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa  other-file.tar.gz
+bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb  cli-v1.0.0.tar.gz
+";
+        assert_eq!(
+            parse_checksum_body(body, "cli-v1.0.0.tar.gz"),
+            Some("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb".to_string())
+        );
+    }
+
+    #[test]
+    fn no_match_returns_none_instead_of_an_unrelated_hash() {
+        let body = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa  other-file.tar.gz\n";
+        assert_eq!(parse_checksum_body(body, "cli-v1.0.0.tar.gz"), None);
+    }
+
+    #[test]
+    fn picks_tar_gz_asset_over_checksum_files() {
+        let release_json = serde_json::json!({
+            "assets": [
+                {"name": "cli-v1.0.0.tar.gz.sha256", "browser_download_url": "https://example.com/cli.tar.gz.sha256"},
+                {"name": "cli-v1.0.0.tar.gz", "browser_download_url": "https://example.com/cli.tar.gz"},
+            ]
+        });
+        let picked = pick_archive_asset(&release_json);
+        assert_eq!(
+            picked,
+            Some((
+                "https://example.com/cli.tar.gz".to_string(),
+                "cli-v1.0.0.tar.gz".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn no_archive_asset_returns_none() {
+        let release_json = serde_json::json!({
+            "assets": [
+                {"name": "CHANGELOG.md", "browser_download_url": "https://example.com/CHANGELOG.md"},
+            ]
+        });
+        assert_eq!(pick_archive_asset(&release_json), None);
+    }
+}
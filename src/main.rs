@@ -1,12 +1,17 @@
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Command;
 use reqwest::blocking::Client;
 use dirs::cache_dir;
+use sha2::Digest;
+
+mod cmd;
+mod integrity;
+mod npm_cache;
+mod sandbox;
+mod version;
 
 const GITHUB_REPO: &str = "0xshariq/rust_package_installer_cli";
-const CLI_VERSION: &str = "latest"; // You can make this configurable
 const CACHE_DIR_NAME: &str = ".package-installer-cli";
 const LOCAL_CLI_DIR: &str = "node_modules/@0xshariq/package-installer";
 
@@ -25,15 +30,69 @@ fn main() {
         } else {
             args.iter().skip(2).cloned().collect::<Vec<String>>()
         };
-        
+
+        // `pi self update` forces a fresh, pinned/latest re-download instead of
+        // forwarding to the cached Node CLI.
+        if cli_args.first().map(String::as_str) == Some("self")
+            && cli_args.get(1).map(String::as_str) == Some("update")
+        {
+            match self_update() {
+                Ok(_) => {
+                    println!("✅ CLI updated successfully!");
+                    std::process::exit(0);
+                }
+                Err(e) => {
+                    println!("❌ Self update failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        // `pi self pin <tag>` / `pi self pin --clear` writes (or removes) the
+        // version-pin.txt config file that desired_version reads.
+        if cli_args.first().map(String::as_str) == Some("self")
+            && cli_args.get(1).map(String::as_str) == Some("pin")
+        {
+            match self_pin(cli_args.get(2).map(String::as_str)) {
+                Ok(message) => {
+                    println!("{}", message);
+                    std::process::exit(0);
+                }
+                Err(e) => {
+                    println!("❌ {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+
         // Ensure CLI is downloaded and cached
         let cli_path = ensure_cli_available().expect("Failed to download or find CLI");
-        
+
+        // `--sandbox`/`--no-network` configure our jail; strip them before the
+        // downloaded CLI sees them, since it doesn't know about either flag.
+        let forwarded_args: Vec<String> = cli_args
+            .iter()
+            .filter(|a| a.as_str() != "--sandbox" && a.as_str() != "--no-network")
+            .cloned()
+            .collect();
+
+        let project_dir = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        let jail = sandbox::Sandbox::from_env_or_args(
+            &cli_args,
+            project_dir,
+            get_cache_dir().unwrap_or_default(),
+        );
+
+        let mut node_command = cmd::Cmd::command("node");
+        node_command.arg(&cli_path).args(&forwarded_args);
+
+        let (mut node_command, sandboxed) = jail.apply(node_command);
+        if sandboxed {
+            println!("🔒 Running the CLI inside a sandbox jail");
+        }
+
         // Run the CLI
-        match Command::new("node")
-            .arg(&cli_path)
-            .args(&cli_args)
-            .status() {
+        match node_command.status() {
             Ok(status) => {
                 std::process::exit(status.code().unwrap_or(1));
             }
@@ -67,14 +126,19 @@ fn ensure_cli_available() -> Result<PathBuf, Box<dyn std::error::Error>> {
     // If no local installation, use cached/global installation
     let cache_dir = get_cache_dir()?;
     let cli_path = cache_dir.join("dist").join("index.js");
-    
+
     // Check if CLI already exists in cache
     if cli_path.exists() {
+        // Only re-download when the pinned/latest release tag differs from the installed one.
+        if let Err(e) = refresh_cache_incrementally(&cache_dir) {
+            println!("⚠️  Incremental refresh failed ({}); using cached copy.", e);
+        }
+
         // Check if dependencies are installed
         if !dependencies_installed(&cache_dir) {
             println!("🔍 CLI found but dependencies not installed.");
             println!("🚀 Attempting to install dependencies automatically...");
-            
+
             match install_dependencies(&cache_dir) {
                 Ok(_) => {
                     println!("✅ Ready to use!");
@@ -101,6 +165,52 @@ fn ensure_cli_available() -> Result<PathBuf, Box<dyn std::error::Error>> {
     }
 }
 
+/// Forces a re-download of the pinned/latest version into a staging
+/// directory and swaps it into place, so a failed download never corrupts
+/// the working cache.
+fn self_update() -> Result<(), Box<dyn std::error::Error>> {
+    let cache_dir = get_cache_dir()?;
+    let parent = cache_dir
+        .parent()
+        .ok_or("Cache directory has no parent to stage an update in")?;
+    let staging_dir = parent.join(format!("{}.staging", CACHE_DIR_NAME));
+
+    if staging_dir.exists() {
+        fs::remove_dir_all(&staging_dir)?;
+    }
+    fs::create_dir_all(&staging_dir)?;
+
+    // Carry the pin into staging so download_cli resolves it and the swap keeps it.
+    let pin_file = cache_dir.join(version::VERSION_PIN_FILE);
+    if pin_file.exists() {
+        fs::copy(&pin_file, staging_dir.join(version::VERSION_PIN_FILE))?;
+    }
+
+    println!("🔁 Forcing update to pinned/latest version...");
+    download_cli(&staging_dir)?;
+
+    atomic_swap_into_cache(&cache_dir, &staging_dir)
+}
+
+/// Sets or clears the pinned version recorded in the cache dir. `tag` of
+/// `Some("--clear")` (or no tag at all) removes the pin; otherwise the tag
+/// is written as-is and picked up by `version::desired_version` on the next
+/// run, unless `PI_CLI_VERSION` overrides it.
+fn self_pin(tag: Option<&str>) -> Result<String, Box<dyn std::error::Error>> {
+    let cache_dir = get_cache_dir()?;
+
+    match tag {
+        None | Some("--clear") => {
+            version::clear_pin(&cache_dir)?;
+            Ok("✅ Version pin cleared; future runs will track the latest release.".to_string())
+        }
+        Some(tag) => {
+            version::write_pin(&cache_dir, tag)?;
+            Ok(format!("✅ Pinned to {}; run `pi self update` to fetch it now.", tag))
+        }
+    }
+}
+
 fn check_local_installation() -> Result<PathBuf, Box<dyn std::error::Error>> {
     let current_dir = env::current_dir()?;
     
@@ -151,53 +261,97 @@ fn get_cache_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
     Ok(cache_path)
 }
 
-fn download_cli(cache_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+/// Fetches the release for `tag` (or GitHub's `latest` when `tag == "latest"`),
+/// verifies its uploaded archive asset, and extracts it into `target_dir`.
+/// A release with no uploaded `.tar.gz`/`.tgz` asset falls back to GitHub's
+/// source tarball, which is extracted without verification. Does not install
+/// Node dependencies or record the installed version - callers that want a
+/// fully usable cache do that themselves, so incremental refreshes can stage
+/// into a scratch directory first.
+fn fetch_and_extract(
+    target_dir: &Path,
+    tag: &str,
+) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
     let client = Client::new();
-    
-    // Get the latest release info
-    let release_url = format!("https://api.github.com/repos/{}/releases/{}", GITHUB_REPO, CLI_VERSION);
+
+    let release_url = if tag == "latest" {
+        format!("https://api.github.com/repos/{}/releases/latest", GITHUB_REPO)
+    } else {
+        format!("https://api.github.com/repos/{}/releases/tags/{}", GITHUB_REPO, tag)
+    };
     let release_response = client.get(&release_url)
         .header("User-Agent", "package-installer-cli-rust-wrapper")
         .send()?;
-    
+
     if !release_response.status().is_success() {
         return Err(format!("Failed to fetch release info: {}", release_response.status()).into());
     }
-    
+
     let release_text = release_response.text()?;
     let release_json: serde_json::Value = serde_json::from_str(&release_text)?;
-    
-    // Find the tarball URL
-    let tarball_url = release_json["tarball_url"]
-        .as_str()
-        .ok_or("Could not find tarball URL in release")?;
-    
-    println!("Downloading from: {}", tarball_url);
-    
-    // Download the tarball
-    let tarball_response = client.get(tarball_url)
+
+    // Prefer a real uploaded release asset (e.g. `cli-vX.Y.Z.tar.gz`) over
+    // GitHub's autogenerated `tarball_url` source archive: only an asset the
+    // maintainer actually uploaded can have a meaningful checksum published
+    // alongside it, since `tarball_url` is regenerated on demand and never
+    // itself appears in `assets`.
+    let (download_url, archive_name, require_verification) =
+        match integrity::pick_archive_asset(&release_json) {
+            Some((url, name)) => (url, name, true),
+            None => {
+                println!(
+                    "⚠️  This release has no uploaded archive asset to verify against; falling back to GitHub's unverified source tarball."
+                );
+                let tarball_url = release_json["tarball_url"]
+                    .as_str()
+                    .ok_or("Could not find tarball URL in release")?
+                    .to_string();
+                let name = release_json["tag_name"].as_str().unwrap_or(tag).to_string();
+                (tarball_url, name, false)
+            }
+        };
+
+    println!("Downloading from: {}", download_url);
+
+    // Download the archive
+    let tarball_response = client.get(&download_url)
         .header("User-Agent", "package-installer-cli-rust-wrapper")
         .send()?;
-    
+
     if !tarball_response.status().is_success() {
-        return Err(format!("Failed to download tarball: {}", tarball_response.status()).into());
+        return Err(format!("Failed to download archive: {}", tarball_response.status()).into());
     }
-    
+
     let tarball_bytes = tarball_response.bytes()?;
-    
+
+    // Verify the archive against its SHA256SUMS/*.sha256 asset before we trust
+    // it enough to extract, the same way an npm lockfile pins an `integrity`
+    // hash for each resolved tarball.
+    let temp_dir = target_dir.join("temp");
+    if require_verification {
+        match integrity::verify_tarball(&client, &release_json, &archive_name, &tarball_bytes) {
+            Ok(sri) => println!("🔒 Verified tarball integrity: {}", sri),
+            Err(e) => {
+                if temp_dir.exists() {
+                    fs::remove_dir_all(&temp_dir)?;
+                }
+                return Err(format!("Refusing to extract tarball: {}", e).into());
+            }
+        }
+    }
+
     // Extract the tarball
     let tar = flate2::read::GzDecoder::new(&tarball_bytes[..]);
     let mut archive = tar::Archive::new(tar);
-    
+
     // Extract to a temporary directory first
-    let temp_dir = cache_dir.join("temp");
     if temp_dir.exists() {
         fs::remove_dir_all(&temp_dir)?;
     }
     fs::create_dir_all(&temp_dir)?;
-    
+
     archive.unpack(&temp_dir)?;
-    
+
     // Find the extracted directory (GitHub creates a directory with repo name and commit hash)
     let mut extracted_dir = None;
     for entry in fs::read_dir(&temp_dir)? {
@@ -207,19 +361,167 @@ fn download_cli(cache_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
             break;
         }
     }
-    
+
     let extracted_dir = extracted_dir.ok_or("Could not find extracted directory")?;
-    
+
     // Copy the entire project to cache (including package.json and dependencies info)
-    copy_dir_all(&extracted_dir, cache_dir)?;
-    
-    // Install Node.js dependencies
-    println!("Installing Node.js dependencies...");
-    install_dependencies(cache_dir)?;
-    
+    copy_dir_all(&extracted_dir, target_dir)?;
+
     // Clean up temp directory
     fs::remove_dir_all(&temp_dir)?;
-    
+
+    Ok(release_json)
+}
+
+/// Fetches, extracts, installs dependencies for, and records the installed
+/// version of a fresh CLI copy directly into `cache_dir`.
+fn download_cli(cache_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let desired = version::desired_version(cache_dir);
+    let release_json = fetch_and_extract(cache_dir, &desired)?;
+
+    println!("Installing Node.js dependencies...");
+    install_dependencies(cache_dir)?;
+
+    if let Some(tag) = release_json["tag_name"].as_str() {
+        version::record_installed_version(cache_dir, tag)?;
+    }
+
+    Ok(())
+}
+
+/// Checks whether the pinned/latest release tag differs from the one recorded
+/// in `cache_dir` (in either direction) and, if so, stages that release and
+/// swaps it into place atomically so an interrupted refresh never corrupts
+/// the working cache. When a pinned tag already matches the installed tag,
+/// no network request is made; when tracking latest, the releases API is
+/// queried for the latest tag and the download is skipped if it matches.
+fn refresh_cache_incrementally(cache_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let desired = version::desired_version(cache_dir);
+    let installed = version::installed_version(cache_dir);
+    let is_pinned = desired != "latest";
+
+    if is_pinned && installed.as_deref() == Some(desired.as_str()) {
+        println!("✅ Cached CLI already matches pinned version {}; skipping network.", desired);
+        return Ok(());
+    }
+
+    let target_tag = if is_pinned {
+        desired
+    } else {
+        match version::fetch_latest_tag(&Client::new(), GITHUB_REPO) {
+            Ok(tag) => tag,
+            Err(e) => {
+                println!("⚠️  Could not check for a newer release ({}); using cached copy.", e);
+                return Ok(());
+            }
+        }
+    };
+
+    if installed.as_deref() == Some(target_tag.as_str()) {
+        println!("✅ Cached CLI is already at {}; skipping download.", target_tag);
+        return Ok(());
+    }
+
+    if is_pinned {
+        // A pin can move forward or backward; "newer" would be a lie here.
+        println!(
+            "🔄 Switching to pinned version {} (currently {}); refreshing cache incrementally...",
+            target_tag,
+            installed.as_deref().unwrap_or("unknown")
+        );
+    } else {
+        let is_newer = match (
+            installed.as_deref().and_then(version::SemVer::parse),
+            version::SemVer::parse(&target_tag),
+        ) {
+            (Some(current), Some(latest)) => latest > current,
+            // Unparseable tag on either side: we can't prove direction, so
+            // proceed to refresh rather than get stuck on a stale cache.
+            _ => true,
+        };
+        if is_newer {
+            println!(
+                "🔄 Newer release available ({} -> {}); refreshing cache incrementally...",
+                installed.as_deref().unwrap_or("unknown"),
+                target_tag
+            );
+        } else {
+            println!(
+                "🔄 Release tag changed ({} -> {}); refreshing cache incrementally...",
+                installed.as_deref().unwrap_or("unknown"),
+                target_tag
+            );
+        }
+    }
+
+    let parent = cache_dir
+        .parent()
+        .ok_or("Cache directory has no parent to stage a refresh in")?;
+    let staging_dir = parent.join(format!("{}.staging", CACHE_DIR_NAME));
+    if staging_dir.exists() {
+        fs::remove_dir_all(&staging_dir)?;
+    }
+    fs::create_dir_all(&staging_dir)?;
+
+    // Keep the version pin across the swap below.
+    let pin_file = cache_dir.join(version::VERSION_PIN_FILE);
+    if pin_file.exists() {
+        fs::copy(&pin_file, staging_dir.join(version::VERSION_PIN_FILE))?;
+    }
+
+    let release_json = fetch_and_extract(&staging_dir, &target_tag)?;
+
+    // Preserve node_modules when the lockfile hasn't changed, to avoid a redundant reinstall.
+    let lockfile_unchanged = lockfile_hash(cache_dir).is_some()
+        && lockfile_hash(cache_dir) == lockfile_hash(&staging_dir);
+    let old_node_modules = cache_dir.join("node_modules");
+    if lockfile_unchanged && old_node_modules.exists() {
+        println!("♻️  Dependencies unchanged; carrying over the existing node_modules.");
+        copy_dir_all(&old_node_modules, staging_dir.join("node_modules"))?;
+    }
+
+    if !dependencies_installed(&staging_dir) {
+        install_dependencies(&staging_dir)?;
+    }
+
+    if let Some(tag) = release_json["tag_name"].as_str() {
+        version::record_installed_version(&staging_dir, tag)?;
+    }
+
+    atomic_swap_into_cache(cache_dir, &staging_dir)?;
+    println!("✅ Cache refreshed to {}", target_tag);
+    Ok(())
+}
+
+/// SHA-256 of `package-lock.json` under `dir`, used to detect whether
+/// dependencies actually changed between two extracted copies of the CLI.
+fn lockfile_hash(dir: &Path) -> Option<String> {
+    let bytes = fs::read(dir.join("package-lock.json")).ok()?;
+    let digest = sha2::Sha256::digest(&bytes);
+    Some(digest.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Renames `staging_dir` into `cache_dir`'s place, backing up and then
+/// discarding whatever was there before. A `fs::rename` on the same
+/// filesystem is atomic, so a crash mid-refresh can't leave a half-written
+/// cache behind.
+fn atomic_swap_into_cache(cache_dir: &Path, staging_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let parent = cache_dir
+        .parent()
+        .ok_or("Cache directory has no parent to swap into")?;
+    let backup_dir = parent.join(format!("{}.old", CACHE_DIR_NAME));
+
+    if backup_dir.exists() {
+        fs::remove_dir_all(&backup_dir)?;
+    }
+    if cache_dir.exists() {
+        fs::rename(cache_dir, &backup_dir)?;
+    }
+    fs::rename(staging_dir, cache_dir)?;
+    if backup_dir.exists() {
+        fs::remove_dir_all(&backup_dir)?;
+    }
+
     Ok(())
 }
 
@@ -247,33 +549,34 @@ fn install_dependencies(cache_dir: &Path) -> Result<(), Box<dyn std::error::Erro
     
     // Determine which package manager to use
     let package_manager = detect_package_manager(cache_dir);
-    
+
     println!("Installing dependencies using {}...", package_manager);
     println!("This may take a few moments...");
-    
+
+    // Integrity-check every resolvable dependency up front, so a corrupted or
+    // tampered tarball is caught before npm ever touches it. This does not
+    // make the install itself offline-capable - npm's own cache is a
+    // different, internal format ours doesn't try to reproduce - so the
+    // install below still goes over the network as normal.
+    if let Err(e) = npm_cache::prefetch_from_lockfile(cache_dir) {
+        println!("⚠️  Dependency prefetch failed, continuing with a normal install: {}", e);
+    }
+
     // Run the package manager install command
-    let mut cmd = Command::new(&package_manager);
-    
     match package_manager.as_str() {
-        "pnpm" => {
-            cmd.arg("install").arg("--production").arg("--silent");
-        }
-        "yarn" => {
-            cmd.arg("install").arg("--production").arg("--silent");
-        }
-        "npm" => {
-            cmd.arg("install").arg("--production").arg("--silent");
-        }
+        "pnpm" | "yarn" | "npm" => {}
         _ => {
             print_manual_installation_instructions(cache_dir);
             return Err(format!("Unsupported package manager: {}", package_manager).into());
         }
     }
-    
-    let result = cmd
-        .current_dir(cache_dir)
-        .status();
-    
+
+    let result = cmd::Cmd::run_inherited(
+        &package_manager,
+        &["install", "--production", "--silent"],
+        Some(cache_dir),
+    );
+
     match result {
         Ok(status) => {
             if status.success() {
@@ -297,18 +600,18 @@ fn detect_package_manager(cache_dir: &Path) -> String {
     // Check for lock files to determine the package manager
     if cache_dir.join("pnpm-lock.yaml").exists() {
         // Check if pnpm is available
-        if Command::new("pnpm").arg("--version").output().is_ok() {
+        if cmd::Cmd::is_available("pnpm") {
             return "pnpm".to_string();
         }
     }
-    
+
     if cache_dir.join("yarn.lock").exists() {
         // Check if yarn is available
-        if Command::new("yarn").arg("--version").output().is_ok() {
+        if cmd::Cmd::is_available("yarn") {
             return "yarn".to_string();
         }
     }
-    
+
     // Default to npm
     "npm".to_string()
 }
@@ -331,13 +634,13 @@ fn print_manual_installation_instructions(cache_dir: &Path) {
     // Check which package managers are available
     let mut available_managers = Vec::new();
     
-    if Command::new("npm").arg("--version").output().is_ok() {
+    if cmd::Cmd::is_available("npm") {
         available_managers.push("npm install @0xshariq/package-installer");
     }
-    if Command::new("yarn").arg("--version").output().is_ok() {
+    if cmd::Cmd::is_available("yarn") {
         available_managers.push("yarn add @0xshariq/package-installer");
     }
-    if Command::new("pnpm").arg("--version").output().is_ok() {
+    if cmd::Cmd::is_available("pnpm") {
         available_managers.push("pnpm add @0xshariq/package-installer");
     }
     
@@ -0,0 +1,311 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use rayon::prelude::*;
+use reqwest::blocking::Client;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const STORE_DIR_NAME: &str = "npm-store";
+const CONTENT_DIR_NAME: &str = "content";
+const INDEX_FILE_NAME: &str = "index.json";
+
+/// A single dependency resolved from `package-lock.json`: where to fetch it
+/// from and the integrity hash it must match.
+struct LockEntry {
+    name: String,
+    version: String,
+    resolved: String,
+    integrity: String,
+}
+
+/// Returns our own content-addressable dependency store rooted at
+/// `cache_dir`: `content/<algo>/<prefix>/<hash>` plus a flat `index.json`
+/// mapping `name@version` to its content path. This is *not* npm's own
+/// cache format (that's `cacache`'s `content-v2`/`index-v5`, keyed by a hash
+/// of the request rather than the package name, and only ever written by npm
+/// itself) - it exists purely so we can integrity-check every dependency up
+/// front, not to be handed to `npm ci --offline`.
+pub fn store_dir(cache_dir: &Path) -> PathBuf {
+    cache_dir.join(STORE_DIR_NAME)
+}
+
+/// Reads `package-lock.json` next to `cache_dir`'s `package.json` and
+/// downloads every resolvable, non-bundled dependency in parallel, verifying
+/// each against its `integrity` hash before anything else touches it. This
+/// catches a corrupted or tampered tarball before `npm install` ever runs,
+/// but it does not make the subsequent install offline-capable - npm's own
+/// cache is a different, internal format we don't (and shouldn't) try to
+/// reproduce.
+pub fn prefetch_from_lockfile(cache_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let lockfile_path = cache_dir.join("package-lock.json");
+    if !lockfile_path.exists() {
+        return Ok(()); // Nothing to prefetch; the package manager will hit the network itself.
+    }
+
+    let lockfile_text = fs::read_to_string(&lockfile_path)?;
+    let lockfile_json: serde_json::Value = serde_json::from_str(&lockfile_text)?;
+
+    let entries = parse_lockfile_entries(&lockfile_json);
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    println!(
+        "📥 Prefetching {} dependencies into the verified dependency store...",
+        entries.len()
+    );
+
+    let store = store_dir(cache_dir);
+    fs::create_dir_all(store.join(CONTENT_DIR_NAME))?;
+
+    let client = Client::new();
+    let results: Vec<Result<(String, String), String>> = entries
+        .par_iter()
+        .map(|entry| fetch_and_store(&client, &store, entry).map_err(|e| {
+            format!("{}@{}: {}", entry.name, entry.version, e)
+        }))
+        .collect();
+
+    let mut index = load_index(&store);
+    let mut failures = Vec::new();
+    for result in results {
+        match result {
+            Ok((key, content_path)) => {
+                index.insert(key, content_path);
+            }
+            Err(e) => failures.push(e),
+        }
+    }
+    save_index(&store, &index)?;
+
+    if !failures.is_empty() {
+        println!(
+            "⚠️  {} dependencies could not be prefetched or verified:",
+            failures.len()
+        );
+        for failure in &failures {
+            println!("   - {}", failure);
+        }
+    } else {
+        println!("✅ All dependencies prefetched and verified.");
+    }
+
+    Ok(())
+}
+
+/// Supports both lockfile v1 (`dependencies`) and v2+ (`packages`) shapes.
+fn parse_lockfile_entries(lockfile_json: &serde_json::Value) -> Vec<LockEntry> {
+    let mut entries = Vec::new();
+
+    if let Some(packages) = lockfile_json["packages"].as_object() {
+        for (path, meta) in packages {
+            if path.is_empty() {
+                continue; // The root package itself.
+            }
+            if meta["bundled"].as_bool().unwrap_or(false) {
+                continue;
+            }
+            let name = path.rsplit("node_modules/").next().unwrap_or(path).to_string();
+            if let (Some(resolved), Some(integrity), Some(version)) = (
+                meta["resolved"].as_str(),
+                meta["integrity"].as_str(),
+                meta["version"].as_str(),
+            ) {
+                entries.push(LockEntry {
+                    name,
+                    version: version.to_string(),
+                    resolved: resolved.to_string(),
+                    integrity: integrity.to_string(),
+                });
+            }
+        }
+        return entries;
+    }
+
+    if let Some(dependencies) = lockfile_json["dependencies"].as_object() {
+        collect_v1_entries(dependencies, &mut entries);
+    }
+
+    entries
+}
+
+fn collect_v1_entries(dependencies: &serde_json::Map<String, serde_json::Value>, out: &mut Vec<LockEntry>) {
+    for (name, meta) in dependencies {
+        if meta["bundled"].as_bool().unwrap_or(false) {
+            continue;
+        }
+        if let (Some(resolved), Some(integrity), Some(version)) = (
+            meta["resolved"].as_str(),
+            meta["integrity"].as_str(),
+            meta["version"].as_str(),
+        ) {
+            out.push(LockEntry {
+                name: name.clone(),
+                version: version.to_string(),
+                resolved: resolved.to_string(),
+                integrity: integrity.to_string(),
+            });
+        }
+        if let Some(nested) = meta["dependencies"].as_object() {
+            collect_v1_entries(nested, out);
+        }
+    }
+}
+
+/// Downloads `entry.resolved`, verifies it against `entry.integrity`, and
+/// stores it under `store/content/<algo>/<prefix>/<hash>`. Returns the
+/// `name@version` index key and the content-relative path on success.
+fn fetch_and_store(
+    client: &Client,
+    store: &Path,
+    entry: &LockEntry,
+) -> Result<(String, String), Box<dyn std::error::Error>> {
+    let response = client
+        .get(&entry.resolved)
+        .header("User-Agent", "package-installer-cli-rust-wrapper")
+        .send()?;
+    if !response.status().is_success() {
+        return Err(format!("download failed: {}", response.status()).into());
+    }
+    let bytes = response.bytes()?;
+
+    let (algo, expected_hash) = parse_integrity(&entry.integrity)?;
+    let actual_hash = hash_with(algo, &bytes);
+    if actual_hash != expected_hash {
+        let expected_hex: String = expected_hash.iter().map(|b| format!("{:02x}", b)).collect();
+        let actual_hex: String = actual_hash.iter().map(|b| format!("{:02x}", b)).collect();
+        return Err(format!(
+            "integrity mismatch: expected {}-{} but got {}-{}",
+            algo, expected_hex, algo, actual_hex
+        )
+        .into());
+    }
+
+    let hash_hex: String = actual_hash.iter().map(|b| format!("{:02x}", b)).collect();
+    let prefix = &hash_hex[..2.min(hash_hex.len())];
+    let content_dir = store.join(CONTENT_DIR_NAME).join(algo).join(prefix);
+    fs::create_dir_all(&content_dir)?;
+    let content_path = content_dir.join(&hash_hex);
+    fs::write(&content_path, &bytes)?;
+
+    let relative = format!("{}/{}/{}", algo, prefix, hash_hex);
+    Ok((format!("{}@{}", entry.name, entry.version), relative))
+}
+
+/// Parses an npm `integrity` field (`"sha512-<base64>"`) into its algorithm
+/// name and raw digest bytes. When multiple hashes are space-separated, the
+/// strongest supported one wins, matching npm's own preference order.
+fn parse_integrity(integrity: &str) -> Result<(&'static str, Vec<u8>), Box<dyn std::error::Error>> {
+    for preferred in ["sha512", "sha256", "sha1"] {
+        for part in integrity.split_whitespace() {
+            if let Some(b64) = part.strip_prefix(&format!("{}-", preferred)) {
+                let bytes = BASE64.decode(b64)?;
+                return Ok((preferred, bytes));
+            }
+        }
+    }
+    Err(format!("unsupported or missing integrity algorithm in '{}'", integrity).into())
+}
+
+fn hash_with(algo: &str, bytes: &[u8]) -> Vec<u8> {
+    match algo {
+        "sha512" => Sha512::digest(bytes).to_vec(),
+        "sha256" => Sha256::digest(bytes).to_vec(),
+        _ => Sha1::digest(bytes).to_vec(),
+    }
+}
+
+fn load_index(store: &Path) -> HashMap<String, String> {
+    fs::read_to_string(store.join(INDEX_FILE_NAME))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(store: &Path, index: &HashMap<String, String>) -> Result<(), Box<dyn std::error::Error>> {
+    let text = serde_json::to_string_pretty(index)?;
+    fs::write(store.join(INDEX_FILE_NAME), text)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_integrity_prefers_strongest_supported_algorithm() {
+        let (algo, _) = parse_integrity("sha1-AAAAAAAAAAAAAAAAAAAAAAAAAAA= sha512-AAAA").unwrap();
+        assert_eq!(algo, "sha512");
+    }
+
+    #[test]
+    fn parse_integrity_decodes_base64_digest() {
+        let (algo, bytes) = parse_integrity("sha256-AAAA").unwrap();
+        assert_eq!(algo, "sha256");
+        assert_eq!(bytes, BASE64.decode("AAAA").unwrap());
+    }
+
+    #[test]
+    fn parse_integrity_rejects_unsupported_algorithm() {
+        assert!(parse_integrity("md5-AAAA").is_err());
+        assert!(parse_integrity("").is_err());
+    }
+
+    #[test]
+    fn parses_v1_lockfile_dependencies_recursively() {
+        let lockfile_json = serde_json::json!({
+            "dependencies": {
+                "foo": {
+                    "version": "1.0.0",
+                    "resolved": "https://registry.npmjs.org/foo/-/foo-1.0.0.tgz",
+                    "integrity": "sha512-AAAA",
+                    "dependencies": {
+                        "bar": {
+                            "version": "2.0.0",
+                            "resolved": "https://registry.npmjs.org/bar/-/bar-2.0.0.tgz",
+                            "integrity": "sha512-BBBB"
+                        }
+                    }
+                },
+                "baz": {
+                    "version": "3.0.0",
+                    "bundled": true
+                }
+            }
+        });
+
+        let entries = parse_lockfile_entries(&lockfile_json);
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&"foo"));
+        assert!(names.contains(&"bar"));
+    }
+
+    #[test]
+    fn parses_v2_lockfile_packages_skipping_root_and_bundled() {
+        let lockfile_json = serde_json::json!({
+            "packages": {
+                "": {"name": "root-package", "version": "1.0.0"},
+                "node_modules/foo": {
+                    "version": "1.0.0",
+                    "resolved": "https://registry.npmjs.org/foo/-/foo-1.0.0.tgz",
+                    "integrity": "sha512-AAAA"
+                },
+                "node_modules/bundled-dep": {
+                    "version": "1.0.0",
+                    "resolved": "https://registry.npmjs.org/bundled-dep/-/bundled-dep-1.0.0.tgz",
+                    "integrity": "sha512-CCCC",
+                    "bundled": true
+                }
+            }
+        });
+
+        let entries = parse_lockfile_entries(&lockfile_json);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "foo");
+        assert_eq!(entries[0].version, "1.0.0");
+    }
+}
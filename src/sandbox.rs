@@ -0,0 +1,178 @@
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+const SANDBOX_ENV_VAR: &str = "PI_SANDBOX";
+const SANDBOX_NO_NETWORK_ENV_VAR: &str = "PI_SANDBOX_NO_NETWORK";
+const SANDBOX_FLAG: &str = "--sandbox";
+const NO_NETWORK_FLAG: &str = "--no-network";
+
+/// Wraps the spawned Node CLI in a filesystem/network jail, following the
+/// bubblewrap-jail approach used by secure AUR helpers: only the project
+/// directory and the cache dir are bind-mounted read/write, everything else
+/// is read-only, and network access can be toggled off entirely.
+pub struct Sandbox {
+    enabled: bool,
+    allow_network: bool,
+    project_dir: PathBuf,
+    cache_dir: PathBuf,
+}
+
+impl Sandbox {
+    /// Reads `--sandbox`/`--no-network` out of the CLI args (so either can be
+    /// forwarded alongside the wrapped CLI's own flags) and falls back to the
+    /// `PI_SANDBOX`/`PI_SANDBOX_NO_NETWORK` env vars.
+    pub fn from_env_or_args(args: &[String], project_dir: PathBuf, cache_dir: PathBuf) -> Sandbox {
+        let enabled = args.iter().any(|a| a == SANDBOX_FLAG) || env_flag(SANDBOX_ENV_VAR);
+        let allow_network =
+            !(args.iter().any(|a| a == NO_NETWORK_FLAG) || env_flag(SANDBOX_NO_NETWORK_ENV_VAR));
+
+        Sandbox {
+            enabled,
+            allow_network,
+            project_dir,
+            cache_dir,
+        }
+    }
+
+    #[cfg(test)]
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Composes `command` with the sandbox wrapper when enabled, preserving
+    /// the original program/args/exit-code behavior. Returns whether the
+    /// command actually ended up wrapped, so callers report what really
+    /// happened instead of what was merely requested - on a platform without
+    /// bubblewrap, this prints a warning and returns `command` unmodified
+    /// rather than failing the run outright.
+    pub fn apply(&self, command: Command) -> (Command, bool) {
+        if !self.enabled {
+            return (command, false);
+        }
+
+        if cfg!(target_os = "linux") && bwrap_available() {
+            (self.wrap_with_bubblewrap(command), true)
+        } else {
+            println!(
+                "⚠️  Sandboxing was requested but bubblewrap (bwrap) isn't available on this platform; running unsandboxed."
+            );
+            (command, false)
+        }
+    }
+
+    fn wrap_with_bubblewrap(&self, command: Command) -> Command {
+        let program = command.get_program().to_owned();
+        let inner_args: Vec<_> = command.get_args().map(|a| a.to_owned()).collect();
+
+        let mut bwrap = Command::new("bwrap");
+        bwrap
+            .arg("--ro-bind")
+            .arg("/")
+            .arg("/")
+            .arg("--dev")
+            .arg("/dev")
+            .arg("--proc")
+            .arg("/proc")
+            .arg("--bind")
+            .arg(&self.project_dir)
+            .arg(&self.project_dir)
+            .arg("--bind")
+            .arg(&self.cache_dir)
+            .arg(&self.cache_dir)
+            .arg("--chdir")
+            .arg(&self.project_dir)
+            .arg("--die-with-parent");
+
+        if !self.allow_network {
+            bwrap.arg("--unshare-net");
+        }
+
+        bwrap.arg(program);
+        bwrap.args(inner_args);
+        bwrap
+    }
+}
+
+fn bwrap_available() -> bool {
+    Command::new("bwrap").arg("--version").output().is_ok()
+}
+
+fn env_flag(name: &str) -> bool {
+    env::var(name)
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // These tests read/write process env vars, which are shared global state;
+    // serialize them so they can't interleave with each other.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    fn clear_env() {
+        env::remove_var(SANDBOX_ENV_VAR);
+        env::remove_var(SANDBOX_NO_NETWORK_ENV_VAR);
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        clear_env();
+        let sandbox = Sandbox::from_env_or_args(&[], PathBuf::from("/proj"), PathBuf::from("/cache"));
+        assert!(!sandbox.is_enabled());
+    }
+
+    #[test]
+    fn flag_enables_sandbox() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        clear_env();
+        let args = vec!["--sandbox".to_string()];
+        let sandbox = Sandbox::from_env_or_args(&args, PathBuf::from("/proj"), PathBuf::from("/cache"));
+        assert!(sandbox.is_enabled());
+    }
+
+    #[test]
+    fn env_var_enables_sandbox_without_flag() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        clear_env();
+        env::set_var(SANDBOX_ENV_VAR, "1");
+        let sandbox = Sandbox::from_env_or_args(&[], PathBuf::from("/proj"), PathBuf::from("/cache"));
+        assert!(sandbox.is_enabled());
+        clear_env();
+    }
+
+    #[test]
+    fn no_network_flag_disables_network_even_with_env_allowing_it() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        clear_env();
+        env::set_var(SANDBOX_NO_NETWORK_ENV_VAR, "0");
+        let args = vec!["--sandbox".to_string(), "--no-network".to_string()];
+        let sandbox = Sandbox::from_env_or_args(&args, PathBuf::from("/proj"), PathBuf::from("/cache"));
+        assert!(sandbox.is_enabled());
+        assert!(!sandbox.allow_network);
+        clear_env();
+    }
+
+    #[test]
+    fn network_allowed_by_default_when_enabled() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        clear_env();
+        let args = vec!["--sandbox".to_string()];
+        let sandbox = Sandbox::from_env_or_args(&args, PathBuf::from("/proj"), PathBuf::from("/cache"));
+        assert!(sandbox.allow_network);
+    }
+
+    #[test]
+    fn apply_is_a_no_op_when_disabled() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        clear_env();
+        let sandbox = Sandbox::from_env_or_args(&[], PathBuf::from("/proj"), PathBuf::from("/cache"));
+        let (command, wrapped) = sandbox.apply(Command::new("node"));
+        assert!(!wrapped);
+        assert_eq!(command.get_program(), "node");
+    }
+}
@@ -0,0 +1,145 @@
+use reqwest::blocking::Client;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// Env var used to pin the CLI to a specific release tag, e.g. `v1.4.2`.
+pub const VERSION_ENV_VAR: &str = "PI_CLI_VERSION";
+
+pub const VERSION_PIN_FILE: &str = "version-pin.txt";
+const INSTALLED_VERSION_FILE: &str = "installed-version.txt";
+
+/// A parsed `major.minor.patch` semver tag, tolerating a leading `v` and any
+/// trailing pre-release/build metadata (`v1.2.3-beta.1` parses as `1.2.3`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SemVer {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl SemVer {
+    pub fn parse(tag: &str) -> Option<SemVer> {
+        let trimmed = tag.trim().trim_start_matches(['v', 'V']);
+        let core = trimmed.split(['-', '+']).next().unwrap_or(trimmed);
+        let mut parts = core.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        let patch = parts.next()?.parse().ok()?;
+        Some(SemVer { major, minor, patch })
+    }
+}
+
+/// Resolves the version the user wants: `PI_CLI_VERSION`, then a pinned
+/// version recorded in the cache dir, then `"latest"`.
+pub fn desired_version(cache_dir: &Path) -> String {
+    if let Ok(pinned) = env::var(VERSION_ENV_VAR) {
+        if !pinned.trim().is_empty() {
+            return pinned;
+        }
+    }
+
+    if let Ok(pinned) = fs::read_to_string(cache_dir.join(VERSION_PIN_FILE)) {
+        let pinned = pinned.trim();
+        if !pinned.is_empty() {
+            return pinned.to_string();
+        }
+    }
+
+    "latest".to_string()
+}
+
+/// Writes `tag` to `version-pin.txt` in `cache_dir`, so future runs resolve
+/// to it via `desired_version` until `PI_CLI_VERSION` overrides it or the
+/// pin is cleared. Set via `pi self pin <tag>`.
+pub fn write_pin(cache_dir: &Path, tag: &str) -> std::io::Result<()> {
+    fs::write(cache_dir.join(VERSION_PIN_FILE), tag.trim())
+}
+
+/// Removes the pin file in `cache_dir`, reverting `desired_version` to
+/// `PI_CLI_VERSION` (if set) or `"latest"`. Set via `pi self pin --clear`.
+pub fn clear_pin(cache_dir: &Path) -> std::io::Result<()> {
+    match fs::remove_file(cache_dir.join(VERSION_PIN_FILE)) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Persists the release tag that is actually installed in `cache_dir`, so
+/// future runs can tell whether a newer release has been published.
+pub fn record_installed_version(cache_dir: &Path, tag: &str) -> std::io::Result<()> {
+    fs::write(cache_dir.join(INSTALLED_VERSION_FILE), tag.trim())
+}
+
+pub fn installed_version(cache_dir: &Path) -> Option<String> {
+    fs::read_to_string(cache_dir.join(INSTALLED_VERSION_FILE))
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+pub fn fetch_latest_tag(
+    client: &Client,
+    github_repo: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let url = format!("https://api.github.com/repos/{}/releases/latest", github_repo);
+    let response = client
+        .get(&url)
+        .header("User-Agent", "package-installer-cli-rust-wrapper")
+        .send()?;
+
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch latest release info: {}", response.status()).into());
+    }
+
+    let release_json: serde_json::Value = response.json()?;
+    release_json["tag_name"]
+        .as_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| "Latest release response had no tag_name".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_leading_v_and_plain_tags() {
+        assert_eq!(
+            SemVer::parse("v1.2.3"),
+            Some(SemVer { major: 1, minor: 2, patch: 3 })
+        );
+        assert_eq!(
+            SemVer::parse("1.2.3"),
+            Some(SemVer { major: 1, minor: 2, patch: 3 })
+        );
+    }
+
+    #[test]
+    fn strips_pre_release_and_build_metadata() {
+        assert_eq!(
+            SemVer::parse("v1.2.3-beta.1"),
+            Some(SemVer { major: 1, minor: 2, patch: 3 })
+        );
+        assert_eq!(
+            SemVer::parse("v1.2.3+build.5"),
+            Some(SemVer { major: 1, minor: 2, patch: 3 })
+        );
+    }
+
+    #[test]
+    fn rejects_non_semver_tags() {
+        assert_eq!(SemVer::parse("latest"), None);
+        assert_eq!(SemVer::parse("v1.2"), None);
+        assert_eq!(SemVer::parse(""), None);
+    }
+
+    #[test]
+    fn compares_component_by_component() {
+        assert!(SemVer::parse("v2.0.0").unwrap() > SemVer::parse("v1.9.9").unwrap());
+        assert!(SemVer::parse("v1.10.0").unwrap() > SemVer::parse("v1.9.0").unwrap());
+        assert!(SemVer::parse("v1.2.4").unwrap() > SemVer::parse("v1.2.3").unwrap());
+        assert!(SemVer::parse("v1.2.3").unwrap() == SemVer::parse("1.2.3").unwrap());
+    }
+}